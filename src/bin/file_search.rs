@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::mpsc::{self, Receiver};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -12,18 +14,214 @@ use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
+use notify::{Event as FsEvent, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use rayon::iter::{ParallelBridge, ParallelIterator};
 use ratatui::{
     DefaultTerminal, Frame,
     layout::{Constraint, Direction, Layout},
     style::{Color, Style, Stylize},
-    text::Line,
+    text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
 };
+use serde::Deserialize;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
 use walkdir::WalkDir;
 
 const MAX_RESULTS: usize = 10_000;
 const SPINNER: [&str; 8] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧"];
+/// Flush an in-flight hits batch once it reaches this size...
+const HITS_BATCH_SIZE: usize = 200;
+/// ...or, when the next hit is pushed, once this much time has passed since the last
+/// flush, whichever comes first.
+const HITS_BATCH_INTERVAL: Duration = Duration::from_millis(100);
+/// How many scanned files between live `SearchMsg::Progress` updates.
+const PROGRESS_INTERVAL: usize = 500;
+/// How many leading bytes to sniff for a NUL byte when skipping binary files in content mode.
+const CONTENT_SNIFF_BYTES: usize = 8 * 1024;
+/// How many lines of a file to render in the preview pane.
+const PREVIEW_MAX_LINES: usize = 200;
+/// Wait this long after the selection stops moving before re-rendering the preview,
+/// so rapid Up/Down scrolling doesn't thrash the disk.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(120);
+/// Files larger than this are skipped in content mode to keep a full-disk grep bounded.
+const CONTENT_MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// Wait this long after the last filesystem event for a given path before acting on
+/// it, so a burst of events for the same path (git checkout, save-with-backup) only
+/// triggers one reconciliation pass instead of one per raw event.
+const FS_EVENT_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Themeable colors and spinner frames, with the current hardcoded look as defaults.
+#[derive(Debug, Clone)]
+struct UiTheme {
+    foreground: Color,
+    focus: Color,
+    highlight_bg: Color,
+    highlight_fg: Color,
+    status: Color,
+    spinner: Vec<String>,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self {
+            foreground: Color::LightMagenta,
+            focus: Color::Yellow,
+            highlight_bg: Color::Blue,
+            highlight_fg: Color::White,
+            status: Color::Cyan,
+            spinner: SPINNER.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// Resolved, ready-to-use configuration: [`UiTheme`] plus the behavior knobs that
+/// used to be hardcoded constants (result cap, poll interval, default root, symlinks).
+#[derive(Debug, Clone)]
+struct Config {
+    theme: UiTheme,
+    max_results: usize,
+    poll_interval: Duration,
+    default_root: String,
+    follow_symlinks: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: UiTheme::default(),
+            max_results: MAX_RESULTS,
+            poll_interval: Duration::from_millis(120),
+            default_root: String::new(),
+            follow_symlinks: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads `config.toml` from the platform config dir. A missing or unreadable file,
+    /// or one that does not parse as TOML of the expected shape, yields all defaults;
+    /// a parsed file falls back piece-by-piece via [`Config::merge`].
+    fn load() -> Self {
+        let defaults = Self::default();
+
+        let Some(path) = config_path() else {
+            return defaults;
+        };
+        let Ok(raw) = fs::read_to_string(&path) else {
+            return defaults;
+        };
+        let Ok(file) = toml::from_str::<ConfigFile>(&raw) else {
+            return defaults;
+        };
+
+        Self::merge(defaults, file)
+    }
+
+    /// Merges a parsed [`ConfigFile`] over `defaults`, field by field: a present,
+    /// valid field in `file` overrides the default, anything missing or
+    /// unparseable (e.g. an invalid color name) falls back to `defaults`.
+    fn merge(defaults: Self, file: ConfigFile) -> Self {
+        Self {
+            theme: UiTheme {
+                foreground: file
+                    .foreground
+                    .as_deref()
+                    .and_then(parse_color)
+                    .unwrap_or(defaults.theme.foreground),
+                focus: file
+                    .focus
+                    .as_deref()
+                    .and_then(parse_color)
+                    .unwrap_or(defaults.theme.focus),
+                highlight_bg: file
+                    .highlight_bg
+                    .as_deref()
+                    .and_then(parse_color)
+                    .unwrap_or(defaults.theme.highlight_bg),
+                highlight_fg: file
+                    .highlight_fg
+                    .as_deref()
+                    .and_then(parse_color)
+                    .unwrap_or(defaults.theme.highlight_fg),
+                status: file
+                    .status
+                    .as_deref()
+                    .and_then(parse_color)
+                    .unwrap_or(defaults.theme.status),
+                spinner: file
+                    .spinner
+                    .filter(|frames| !frames.is_empty())
+                    .unwrap_or(defaults.theme.spinner),
+            },
+            max_results: file.max_results.unwrap_or(defaults.max_results),
+            poll_interval: file
+                .poll_interval_ms
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.poll_interval),
+            default_root: file.default_root.unwrap_or(defaults.default_root),
+            follow_symlinks: file.follow_symlinks.unwrap_or(defaults.follow_symlinks),
+        }
+    }
+}
+
+/// On-disk shape of `config.toml`. Every field is optional so a partial file only
+/// overrides what it mentions.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    foreground: Option<String>,
+    focus: Option<String>,
+    highlight_bg: Option<String>,
+    highlight_fg: Option<String>,
+    status: Option<String>,
+    spinner: Option<Vec<String>>,
+    max_results: Option<usize>,
+    poll_interval_ms: Option<u64>,
+    default_root: Option<String>,
+    follow_symlinks: Option<bool>,
+}
+
+fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("file_search").join("config.toml"))
+}
+
+/// Parses `#rrggbb` hex or a handful of named colors; anything else falls back to the default.
+fn parse_color(raw: &str) -> Option<Color> {
+    let raw = raw.trim();
+    if let Some(hex) = raw.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        return Some(Color::Rgb(r, g, b));
+    }
+
+    match raw.to_lowercase().as_str() {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "gray" | "grey" => Some(Color::Gray),
+        "darkgray" | "darkgrey" => Some(Color::DarkGray),
+        "lightred" => Some(Color::LightRed),
+        "lightgreen" => Some(Color::LightGreen),
+        "lightyellow" => Some(Color::LightYellow),
+        "lightblue" => Some(Color::LightBlue),
+        "lightmagenta" => Some(Color::LightMagenta),
+        "lightcyan" => Some(Color::LightCyan),
+        "white" => Some(Color::White),
+        "reset" => Some(Color::Reset),
+        _ => None,
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Focus {
@@ -32,54 +230,182 @@ enum Focus {
     Results,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    Include,
+    Fuzzy,
+    Content,
+}
+
+impl SearchMode {
+    fn label(&self) -> &'static str {
+        match self {
+            SearchMode::Include => "include",
+            SearchMode::Fuzzy => "fuzzy",
+            SearchMode::Content => "content",
+        }
+    }
+
+    fn cycled(self) -> Self {
+        match self {
+            SearchMode::Include => SearchMode::Fuzzy,
+            SearchMode::Fuzzy => SearchMode::Content,
+            SearchMode::Content => SearchMode::Include,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct SearchOutput {
     scanned: usize,
-    matched: Vec<String>,
 }
 
+/// Incremental updates sent from a search worker thread to the UI thread.
 #[derive(Debug)]
+enum SearchMsg {
+    /// A freshly-matched batch of `(score, path)` pairs.
+    Hits(Vec<(i64, String)>),
+    /// Live scanned-file counter, sent periodically so the UI doesn't look stalled.
+    Progress(usize),
+    /// The worker has finished walking every root.
+    Done(SearchOutput),
+}
+
+/// Buffers matched hits and flushes them as a `SearchMsg::Hits` batch once
+/// [`HITS_BATCH_SIZE`] hits accumulate or [`HITS_BATCH_INTERVAL`] elapses,
+/// whichever comes first. Both checks run only inside `push`; hits left in the
+/// buffer when no further hit arrives are sent by `flush_remaining` at the end of
+/// the walk. Shared by reference across rayon worker threads.
+struct HitBatcher<'a> {
+    tx: &'a Sender<(u64, SearchMsg)>,
+    generation: u64,
+    buf: Mutex<Vec<(i64, String)>>,
+    last_flush: Mutex<Instant>,
+}
+
+impl<'a> HitBatcher<'a> {
+    fn new(tx: &'a Sender<(u64, SearchMsg)>, generation: u64) -> Self {
+        Self {
+            tx,
+            generation,
+            buf: Mutex::new(Vec::new()),
+            last_flush: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn push(&self, hit: (i64, String)) {
+        let mut buf = self.buf.lock().unwrap();
+        buf.push(hit);
+        let due = buf.len() >= HITS_BATCH_SIZE
+            || self.last_flush.lock().unwrap().elapsed() >= HITS_BATCH_INTERVAL;
+        if due {
+            let batch = std::mem::take(&mut *buf);
+            drop(buf);
+            *self.last_flush.lock().unwrap() = Instant::now();
+            let _ = self.tx.send((self.generation, SearchMsg::Hits(batch)));
+        }
+    }
+
+    fn flush_remaining(&self) {
+        let mut buf = self.buf.lock().unwrap();
+        if !buf.is_empty() {
+            let batch = std::mem::take(&mut *buf);
+            let _ = self.tx.send((self.generation, SearchMsg::Hits(batch)));
+        }
+    }
+}
+
 struct App {
     query: String,
     root: String,
     focus: Focus,
+    mode: SearchMode,
     status: String,
     results: Vec<String>,
+    scored: Vec<(i64, String)>,
     results_state: ListState,
-    search_rx: Option<Receiver<(SearchOutput, String)>>,
+    search_rx: Option<Receiver<(u64, SearchMsg)>>,
+    search_scope: String,
+    search_generation: u64,
+    cancel_flag: Option<Arc<AtomicBool>>,
     searching: bool,
+    scanned: usize,
     spinner_idx: usize,
     started_at: Option<Instant>,
+    syntax_set: SyntaxSet,
+    preview_theme: Theme,
+    preview_lines: Vec<Line<'static>>,
+    preview_selected: Option<usize>,
+    preview_dirty_since: Option<(Instant, Option<usize>)>,
+    icons_enabled: bool,
+    active_query: String,
+    watch_roots: Vec<PathBuf>,
+    watcher: Option<RecommendedWatcher>,
+    fs_rx: Option<Receiver<notify::Result<FsEvent>>>,
+    /// Per-path debounce state for pending filesystem events: when they were last
+    /// seen and whether the latest sighting was a removal.
+    fs_pending: HashMap<String, (Instant, bool)>,
+    config: Config,
+    /// Icons for `results`, same index, recomputed only when `results` changes
+    /// instead of on every redraw (icon lookup stats the filesystem).
+    result_icons: Vec<FileIcon>,
 }
 
-impl Default for App {
-    fn default() -> Self {
+impl App {
+    fn new() -> Self {
+        let theme_set = ThemeSet::load_defaults();
+        let preview_theme = theme_set
+            .themes
+            .get("base16-ocean.dark")
+            .or_else(|| theme_set.themes.values().next())
+            .expect("syntect ships at least one default theme")
+            .clone();
+        let config = Config::load();
+
         Self {
             query: String::new(),
-            root: String::new(),
+            root: config.default_root.clone(),
             focus: Focus::Query,
-            status: "Nhập query, Enter để search, Tab đổi ô, Esc để thoát".to_string(),
+            mode: SearchMode::Include,
+            status: "Nhập query, Enter để search, Tab đổi ô, F2 đổi mode, F3 đổi icon, Esc để huỷ/thoát"
+                .to_string(),
             results: Vec::new(),
+            scored: Vec::new(),
             results_state: ListState::default(),
             search_rx: None,
+            search_scope: String::new(),
+            search_generation: 0,
+            cancel_flag: None,
             searching: false,
+            scanned: 0,
             spinner_idx: 0,
             started_at: None,
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            preview_theme,
+            preview_lines: Vec::new(),
+            preview_selected: None,
+            preview_dirty_since: None,
+            icons_enabled: true,
+            active_query: String::new(),
+            watch_roots: Vec::new(),
+            watcher: None,
+            fs_rx: None,
+            fs_pending: HashMap::new(),
+            config,
+            result_icons: Vec::new(),
         }
     }
 }
 
 impl App {
     fn start_search(&mut self) {
-        if self.searching {
-            self.status = "Search đang chạy, đợi xíu nha...".to_string();
-            return;
-        }
+        self.cancel_in_flight_search();
 
         let query = self.query.trim();
         if query.is_empty() {
             self.status = "Query đang trống. Nhập text để search.".to_string();
             self.results.clear();
+            self.result_icons.clear();
             self.results_state.select(None);
             return;
         }
@@ -94,90 +420,353 @@ impl App {
         if roots.is_empty() {
             self.status = "Không tìm thấy root hợp lệ để search.".to_string();
             self.results.clear();
+            self.result_icons.clear();
             self.results_state.select(None);
             return;
         }
 
         let query_owned = query.to_string();
+        self.active_query = query_owned.clone();
+        self.watch_roots = roots.clone();
         let base_scope = if search_root.is_empty() {
             "toàn bộ computer".to_string()
         } else {
             search_root.to_string()
         };
+        let mode = self.mode;
+        let max_results = self.config.max_results;
+        let follow_symlinks = self.config.follow_symlinks;
+        let generation = self.search_generation + 1;
+        self.search_generation = generation;
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        self.cancel_flag = Some(cancel_flag.clone());
 
         let (tx, rx) = mpsc::channel();
         thread::spawn(move || {
-            let output = search_files_include(&query_owned, &roots, MAX_RESULTS);
-            let _ = tx.send((output, base_scope));
+            let output = match mode {
+                SearchMode::Include => search_files_include(
+                    &query_owned,
+                    &roots,
+                    max_results,
+                    follow_symlinks,
+                    &tx,
+                    generation,
+                    &cancel_flag,
+                ),
+                SearchMode::Fuzzy => search_files_fuzzy(
+                    &query_owned,
+                    &roots,
+                    max_results,
+                    follow_symlinks,
+                    &tx,
+                    generation,
+                    &cancel_flag,
+                ),
+                SearchMode::Content => search_files_content(
+                    &query_owned,
+                    &roots,
+                    max_results,
+                    follow_symlinks,
+                    &tx,
+                    generation,
+                    &cancel_flag,
+                ),
+            };
+            let _ = tx.send((generation, SearchMsg::Done(output)));
         });
 
         self.search_rx = Some(rx);
+        self.search_scope = base_scope;
         self.searching = true;
+        self.scanned = 0;
+        self.scored.clear();
+        self.results.clear();
+        self.result_icons.clear();
+        self.results_state.select(None);
+        self.preview_lines.clear();
+        self.preview_selected = None;
+        self.preview_dirty_since = None;
         self.spinner_idx = 0;
         self.started_at = Some(Instant::now());
         self.status = "Đã bắt đầu search đa luồng".to_string();
     }
 
+    /// Cancels any search already in flight so a fresh one can start cleanly.
+    fn cancel_in_flight_search(&mut self) {
+        if let Some(flag) = self.cancel_flag.take() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        self.search_rx = None;
+        self.searching = false;
+        self.started_at = None;
+        self.stop_watching();
+    }
+
+    fn stop_watching(&mut self) {
+        self.watcher = None;
+        self.fs_rx = None;
+        self.fs_pending.clear();
+    }
+
+    /// Starts watching every root in `roots` for create/remove/rename events once a
+    /// search over them has completed, so the result set stays accurate as files come
+    /// and go. This covers the whole-computer case (one recursive watch per drive/root
+    /// on a single watcher) as
+    /// well as a single scoped root. Individual roots that fail to watch (e.g. a
+    /// drive that went away) are skipped; watching only fails outright if none of
+    /// the roots could be watched.
+    fn start_watching(&mut self, roots: &[PathBuf]) {
+        self.stop_watching();
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        });
+        let Ok(mut watcher) = watcher else {
+            return;
+        };
+
+        let mut watching_any = false;
+        for root in roots {
+            if watcher.watch(root, RecursiveMode::Recursive).is_ok() {
+                watching_any = true;
+            }
+        }
+        if !watching_any {
+            return;
+        }
+
+        self.watcher = Some(watcher);
+        self.fs_rx = Some(rx);
+    }
+
+    /// Drains pending filesystem events into `fs_pending`, then reconciles paths
+    /// that have been quiet for [`FS_EVENT_DEBOUNCE`] against the current result
+    /// set: drop paths that vanished, add freshly-created paths that match the
+    /// active query and strategy.
+    fn drain_fs_events(&mut self) {
+        let Some(rx) = &self.fs_rx else {
+            return;
+        };
+
+        while let Ok(res) = rx.try_recv() {
+            let Ok(event) = res else {
+                continue;
+            };
+
+            let is_removal = matches!(event.kind, EventKind::Remove(_));
+            for path in &event.paths {
+                let p = path.to_string_lossy().to_string();
+                let removed = is_removal || !path.is_file();
+                self.fs_pending.insert(p, (Instant::now(), removed));
+            }
+        }
+
+        let query_lower = self.active_query.to_lowercase();
+        let mode = self.mode;
+        let mut changed = false;
+
+        let mut ready = Vec::new();
+        self.fs_pending.retain(|p, (last_seen, removed)| {
+            if last_seen.elapsed() < FS_EVENT_DEBOUNCE {
+                return true;
+            }
+            ready.push((p.clone(), *removed));
+            false
+        });
+
+        for (p, removed) in ready {
+            if removed {
+                let before = self.scored.len();
+                self.scored
+                    .retain(|(_, existing)| preview_target_path(mode, existing) != p);
+                changed |= self.scored.len() != before;
+                continue;
+            }
+
+            if self.scored.iter().any(|(_, existing)| existing == &p) {
+                continue;
+            }
+            if let Some(score) = matches_query(mode, &query_lower, &p) {
+                self.scored.push((score, p));
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.scored
+                .sort_unstable_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            self.results = self.scored.iter().map(|(_, p)| p.clone()).collect();
+            self.sync_result_icons();
+            if self.results_state.selected().is_none() && !self.results.is_empty() {
+                self.results_state.select(Some(0));
+            }
+        }
+    }
+
+    /// Aborts the running search in response to a user cancel request.
+    fn cancel_search(&mut self) {
+        if !self.searching {
+            return;
+        }
+        self.cancel_in_flight_search();
+        self.status = "Đã huỷ search.".to_string();
+    }
+
     fn tick(&mut self) {
         if !self.searching {
             return;
         }
 
-        self.spinner_idx = (self.spinner_idx + 1) % SPINNER.len();
+        self.spinner_idx = (self.spinner_idx + 1) % self.config.theme.spinner.len();
 
+        // Drain the channel into a local Vec first so the borrow of `self.search_rx`
+        // ends before we need `&mut self` (e.g. `sync_result_icons`) below.
+        let mut messages = Vec::new();
+        let mut disconnected = false;
         if let Some(rx) = &self.search_rx {
-            match rx.try_recv() {
-                Ok((output, base_scope)) => {
-                    self.results = output.matched;
-                    if self.results.is_empty() {
-                        self.results_state.select(None);
-                    } else {
-                        self.results_state.select(Some(0));
+            loop {
+                match rx.try_recv() {
+                    Ok(msg @ (_, SearchMsg::Done(_))) => {
+                        messages.push(msg);
+                        break;
+                    }
+                    Ok(msg) => messages.push(msg),
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        disconnected = true;
+                        break;
                     }
-                    let elapsed = self
-                        .started_at
-                        .map(|t| t.elapsed().as_secs_f32())
-                        .unwrap_or_default();
-
-                    self.status = format!(
-                        "Done: {} kết quả / {} file đã scan trong {} ({:.2}s)",
-                        self.results.len(),
-                        output.scanned,
-                        base_scope,
-                        elapsed
-                    );
-
-                    self.searching = false;
-                    self.search_rx = None;
-                    self.started_at = None;
                 }
-                Err(mpsc::TryRecvError::Empty) => {}
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    self.searching = false;
-                    self.search_rx = None;
-                    self.started_at = None;
-                    self.status = "Search thread bị ngắt kết nối.".to_string();
+            }
+        }
+
+        let mut done = None;
+        for (generation, msg) in messages {
+            if generation != self.search_generation {
+                // Message from a search that was cancelled or superseded; drop it.
+                continue;
+            }
+            match msg {
+                SearchMsg::Hits(batch) => {
+                    self.scored.extend(batch);
+                    self.scored
+                        .sort_unstable_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                    self.results = self.scored.iter().map(|(_, p)| p.clone()).collect();
+                    self.sync_result_icons();
+                    if self.results_state.selected().is_none() && !self.results.is_empty() {
+                        self.results_state.select(Some(0));
+                    }
                 }
+                SearchMsg::Progress(scanned) => self.scanned = scanned,
+                SearchMsg::Done(output) => done = Some(output),
             }
         }
+
+        if let Some(output) = done {
+            self.scanned = output.scanned;
+            let elapsed = self
+                .started_at
+                .map(|t| t.elapsed().as_secs_f32())
+                .unwrap_or_default();
+
+            self.status = format!(
+                "Done: {} kết quả / {} file đã scan trong {} ({:.2}s)",
+                self.results.len(),
+                output.scanned,
+                self.search_scope,
+                elapsed
+            );
+
+            self.searching = false;
+            self.search_rx = None;
+            self.started_at = None;
+
+            if !self.watch_roots.is_empty() {
+                let roots = self.watch_roots.clone();
+                self.start_watching(&roots);
+            }
+        } else if disconnected {
+            self.searching = false;
+            self.search_rx = None;
+            self.started_at = None;
+            self.status = "Search thread bị ngắt kết nối.".to_string();
+        }
+    }
+
+    /// Re-renders the preview pane once the selection has settled on one result
+    /// for at least [`PREVIEW_DEBOUNCE`], so rapid scrolling doesn't thrash the disk.
+    fn update_preview(&mut self) {
+        let selected = self.results_state.selected();
+        if selected == self.preview_selected {
+            self.preview_dirty_since = None;
+            return;
+        }
+
+        let due = match self.preview_dirty_since {
+            Some((since, pending)) if pending == selected => since.elapsed() >= PREVIEW_DEBOUNCE,
+            _ => {
+                // First change, or the selection moved again: restart the wait.
+                self.preview_dirty_since = Some((Instant::now(), selected));
+                false
+            }
+        };
+        if !due {
+            return;
+        }
+
+        self.preview_lines = selected
+            .and_then(|i| self.results.get(i))
+            .map(|raw| render_preview(raw, self.mode, &self.syntax_set, &self.preview_theme))
+            .unwrap_or_default();
+        self.preview_selected = selected;
+        self.preview_dirty_since = None;
     }
 
     fn status_line(&self) -> String {
         if self.searching {
-            let spin = SPINNER[self.spinner_idx];
+            let spin = &self.config.theme.spinner[self.spinner_idx];
             let elapsed = self
                 .started_at
                 .map(|t| t.elapsed().as_secs_f32())
                 .unwrap_or_default();
             return format!(
-                "{} Searching... {:.1}s (multi-thread, include strategy)",
-                spin, elapsed
+                "{} Searching... {:.1}s, {} kết quả / {} file đã scan (multi-thread, {} strategy)",
+                spin,
+                elapsed,
+                self.results.len(),
+                self.scanned,
+                self.mode.label()
             );
         }
 
         self.status.clone()
     }
 
+    fn toggle_mode(&mut self) {
+        self.mode = self.mode.cycled();
+        self.status = format!("Đã đổi sang mode: {}", self.mode.label());
+    }
+
+    fn toggle_icons(&mut self) {
+        self.icons_enabled = !self.icons_enabled;
+        self.status = format!(
+            "Icon hiển thị: {} (dùng cho terminal có Nerd Font)",
+            if self.icons_enabled { "bật" } else { "tắt" }
+        );
+    }
+
+    /// Recomputes `result_icons` to match `self.results`. Each icon lookup stats
+    /// the filesystem, so this is called once whenever `results` changes rather
+    /// than from `draw`, which would otherwise re-stat every entry every frame.
+    fn sync_result_icons(&mut self) {
+        self.result_icons = self
+            .results
+            .iter()
+            .map(|s| icon_for_path(preview_target_path(self.mode, s)))
+            .collect();
+    }
+
     fn select_next(&mut self) {
         if self.results.is_empty() {
             self.results_state.select(None);
@@ -211,14 +800,28 @@ impl App {
             return;
         };
 
-        let Some(selected) = self.results.get(i) else {
+        let Some(selected) = self.results.get(i).cloned() else {
             self.status = "Item được chọn không hợp lệ.".to_string();
             return;
         };
 
-        match open_in_file_explorer(selected) {
+        let target = preview_target_path(self.mode, &selected).to_string();
+        if !Path::new(&target).exists() {
+            self.status = format!("File không còn tồn tại (đã bị xoá/đổi tên): {}", target);
+            self.scored.retain(|(_, p)| p != &selected);
+            self.results.remove(i);
+            self.result_icons.remove(i);
+            self.results_state.select(if self.results.is_empty() {
+                None
+            } else {
+                Some(i.min(self.results.len() - 1))
+            });
+            return;
+        }
+
+        match open_in_file_explorer(&target) {
             Ok(()) => {
-                self.status = format!("Đã mở Explorer tại file: {}", selected);
+                self.status = format!("Đã mở Explorer tại file: {}", target);
             }
             Err(err) => {
                 self.status = format!("Mở Explorer thất bại: {}", err);
@@ -312,56 +915,441 @@ fn root_work_items(root: &Path) -> Vec<PathBuf> {
     items
 }
 
-fn search_files_include(query: &str, roots: &[PathBuf], max_results: usize) -> SearchOutput {
+/// Bumps the scanned counter and emits a throttled `SearchMsg::Progress` update.
+fn record_scan(scanned: &AtomicUsize, tx: &Sender<(u64, SearchMsg)>, generation: u64) {
+    let n = scanned.fetch_add(1, Ordering::Relaxed) + 1;
+    if n % PROGRESS_INTERVAL == 0 {
+        let _ = tx.send((generation, SearchMsg::Progress(n)));
+    }
+}
+
+fn search_files_include(
+    query: &str,
+    roots: &[PathBuf],
+    max_results: usize,
+    follow_symlinks: bool,
+    tx: &Sender<(u64, SearchMsg)>,
+    generation: u64,
+    cancel: &AtomicBool,
+) -> SearchOutput {
     let query_lower = query.to_lowercase();
     let scanned = AtomicUsize::new(0);
     let hits = AtomicUsize::new(0);
+    let batcher = HitBatcher::new(tx, generation);
 
     let work_items: Vec<PathBuf> = roots.iter().flat_map(|r| root_work_items(r)).collect();
 
-    let matched = work_items
-        .into_iter()
-        .par_bridge()
-        .flat_map_iter(|item| {
-            if item.is_file() {
-                scanned.fetch_add(1, Ordering::Relaxed);
-                let s = item.to_string_lossy().to_string();
-                if s.to_lowercase().contains(&query_lower) {
-                    let prev = hits.fetch_add(1, Ordering::Relaxed);
-                    if prev < max_results {
-                        return vec![s];
-                    }
-                }
-                return Vec::new();
+    let check = |path: String| {
+        if path.to_lowercase().contains(&query_lower) {
+            let prev = hits.fetch_add(1, Ordering::Relaxed);
+            if prev < max_results {
+                batcher.push((0i64, path));
             }
+        }
+    };
 
-            WalkDir::new(item)
-                .follow_links(false)
-                .into_iter()
-                .filter_map(Result::ok)
-                .filter(|entry| entry.file_type().is_file())
-                .filter_map(|entry| {
-                    scanned.fetch_add(1, Ordering::Relaxed);
-                    let p = entry.path().to_string_lossy().to_string();
-                    if p.to_lowercase().contains(&query_lower) {
-                        let prev = hits.fetch_add(1, Ordering::Relaxed);
-                        if prev < max_results {
-                            return Some(p);
-                        }
-                    }
-                    None
-                })
-                .collect::<Vec<String>>()
+    work_items.into_iter().par_bridge().for_each(|item| {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if item.is_file() {
+            record_scan(&scanned, tx, generation);
+            check(item.to_string_lossy().to_string());
+            return;
+        }
+
+        WalkDir::new(item)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_map(Result::ok)
+            .take_while(|_| !cancel.load(Ordering::Relaxed))
+            .filter(|entry| entry.file_type().is_file())
+            .for_each(|entry| {
+                record_scan(&scanned, tx, generation);
+                check(entry.path().to_string_lossy().to_string());
+            });
+    });
+
+    batcher.flush_remaining();
+
+    SearchOutput {
+        scanned: scanned.load(Ordering::Relaxed),
+    }
+}
+
+/// Scores `candidate` against `query_lower` as an ordered subsequence match (fzf-style).
+///
+/// `query_lower` must already be lowercased; `candidate` must keep its original case
+/// so the word-boundary bonus can detect camelCase transitions (matching itself is
+/// still case-insensitive).
+///
+/// Returns `None` if not every char of `query_lower` appears in order in `candidate`.
+/// Otherwise returns a score that rewards consecutive runs and word-boundary
+/// matches, and lightly penalizes gaps between matched chars.
+fn fuzzy_score(query_lower: &str, candidate: &str) -> Option<i64> {
+    if query_lower.is_empty() {
+        return Some(0);
+    }
+
+    let query_chars: Vec<char> = query_lower.chars().collect();
+    let cand_chars: Vec<char> = candidate.chars().collect();
+    let cand_lower: Vec<char> = candidate.to_lowercase().chars().collect();
+    if cand_lower.len() != cand_chars.len() {
+        // Lowercasing changed the char count (rare non-ASCII edge case); fall back
+        // to a plain case-insensitive match via the lowercased string alone.
+        return fuzzy_score(query_lower, &candidate.to_lowercase());
+    }
+
+    let mut score: i64 = 0;
+    let mut qi = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (ci, &c) in cand_lower.iter().enumerate() {
+        if qi >= query_chars.len() {
+            break;
+        }
+        if c != query_chars[qi] {
+            continue;
+        }
+
+        score += 1;
+
+        let is_boundary = ci == 0
+            || matches!(cand_chars[ci - 1], '/' | '\\' | '_' | '-' | '.')
+            || (cand_chars[ci - 1].is_lowercase() && cand_chars[ci].is_uppercase());
+        if is_boundary {
+            score += 8;
+        }
+
+        match last_match {
+            Some(prev) if prev + 1 == ci => score += 5,
+            Some(prev) => score -= (ci - prev - 1) as i64,
+            None => {}
+        }
+
+        last_match = Some(ci);
+        qi += 1;
+    }
+
+    if qi < query_chars.len() {
+        return None;
+    }
+
+    Some(score)
+}
+
+fn search_files_fuzzy(
+    query: &str,
+    roots: &[PathBuf],
+    max_results: usize,
+    follow_symlinks: bool,
+    tx: &Sender<(u64, SearchMsg)>,
+    generation: u64,
+    cancel: &AtomicBool,
+) -> SearchOutput {
+    let query_lower = query.to_lowercase();
+    let scanned = AtomicUsize::new(0);
+    let hits = AtomicUsize::new(0);
+    let batcher = HitBatcher::new(tx, generation);
+
+    let work_items: Vec<PathBuf> = roots.iter().flat_map(|r| root_work_items(r)).collect();
+
+    let score_and_push = |path: String| {
+        let Some(score) = fuzzy_score(&query_lower, &path) else {
+            return;
+        };
+        let prev = hits.fetch_add(1, Ordering::Relaxed);
+        if prev < max_results {
+            batcher.push((score, path));
+        }
+    };
+
+    work_items.into_iter().par_bridge().for_each(|item| {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if item.is_file() {
+            record_scan(&scanned, tx, generation);
+            score_and_push(item.to_string_lossy().to_string());
+            return;
+        }
+
+        WalkDir::new(item)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_map(Result::ok)
+            .take_while(|_| !cancel.load(Ordering::Relaxed))
+            .filter(|entry| entry.file_type().is_file())
+            .for_each(|entry| {
+                record_scan(&scanned, tx, generation);
+                score_and_push(entry.path().to_string_lossy().to_string());
+            });
+    });
+
+    batcher.flush_remaining();
+
+    SearchOutput {
+        scanned: scanned.load(Ordering::Relaxed),
+    }
+}
+
+/// Heuristic binary sniff: treat a file as binary if a NUL byte shows up in its first
+/// [`CONTENT_SNIFF_BYTES`] bytes.
+fn looks_binary(path: &Path) -> bool {
+    let Ok(mut file) = fs::File::open(path) else {
+        return true;
+    };
+    let mut buf = [0u8; CONTENT_SNIFF_BYTES];
+    let Ok(n) = file.read(&mut buf) else {
+        return true;
+    };
+    buf[..n].contains(&0)
+}
+
+/// Checks whether `path` matches `query_lower` under `mode`, returning its score if so.
+/// Content mode isn't supported for live reconciliation since it requires re-grepping.
+fn matches_query(mode: SearchMode, query_lower: &str, path: &str) -> Option<i64> {
+    match mode {
+        SearchMode::Include => path.to_lowercase().contains(query_lower).then_some(0),
+        SearchMode::Fuzzy => fuzzy_score(query_lower, path),
+        SearchMode::Content => None,
+    }
+}
+
+/// Content-mode results are `path:line: text`; everything else is already a bare path.
+fn preview_target_path(mode: SearchMode, raw: &str) -> &str {
+    if mode == SearchMode::Content {
+        if let Some(idx) = line_number_separator(raw) {
+            return &raw[..idx];
+        }
+    }
+    raw
+}
+
+/// Finds the byte index of the `:` that separates the path from the `line_number: text`
+/// suffix of a `path:line_number: text` content-mode entry.
+///
+/// Can't just split on the first `:`: on Windows the path itself starts with a drive
+/// letter colon (`C:\Users\...`), which `raw.find(':')` would grab instead. Scans for
+/// the first `:` that is followed by an all-digit run and then another `:`, which the
+/// drive-letter colon never is (it's followed by a path separator, not digits).
+fn line_number_separator(raw: &str) -> Option<usize> {
+    let mut search_from = 0;
+    while let Some(rel) = raw[search_from..].find(':') {
+        let idx = search_from + rel;
+        let rest = &raw[idx + 1..];
+        if let Some(digits_end) = rest.find(':') {
+            if digits_end > 0 && rest[..digits_end].bytes().all(|b| b.is_ascii_digit()) {
+                return Some(idx);
+            }
+        }
+        search_from = idx + 1;
+    }
+    None
+}
+
+/// A Nerd Font glyph plus the color it should render in, keyed by file type.
+#[derive(Debug, Clone, Copy)]
+struct FileIcon {
+    glyph: &'static str,
+    color: Color,
+}
+
+const ICON_DEFAULT: FileIcon = FileIcon {
+    glyph: "\u{f15b}",
+    color: Color::Gray,
+};
+const ICON_FOLDER: FileIcon = FileIcon {
+    glyph: "\u{f07b}",
+    color: Color::Yellow,
+};
+
+/// Looks up the icon for `path` by its lowercase extension, falling back to a
+/// folder glyph for directories and a generic file glyph for unknown types.
+fn icon_for_path(path: &str) -> FileIcon {
+    let path = Path::new(path);
+    if path.is_dir() {
+        return ICON_FOLDER;
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    match ext.as_deref() {
+        Some("rs") => FileIcon {
+            glyph: "\u{e7a8}",
+            color: Color::Rgb(222, 165, 132),
+        },
+        Some("md") => FileIcon {
+            glyph: "\u{e73e}",
+            color: Color::Gray,
+        },
+        Some("js") => FileIcon {
+            glyph: "\u{e74e}",
+            color: Color::Yellow,
+        },
+        Some("ts") => FileIcon {
+            glyph: "\u{e628}",
+            color: Color::Blue,
+        },
+        Some("json") => FileIcon {
+            glyph: "\u{e60b}",
+            color: Color::Yellow,
+        },
+        Some("html") => FileIcon {
+            glyph: "\u{e736}",
+            color: Color::Rgb(227, 79, 38),
+        },
+        Some("css") => FileIcon {
+            glyph: "\u{e749}",
+            color: Color::Blue,
+        },
+        Some("py") => FileIcon {
+            glyph: "\u{e73c}",
+            color: Color::Blue,
+        },
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("svg") => FileIcon {
+            glyph: "\u{f1c5}",
+            color: Color::Magenta,
+        },
+        _ => ICON_DEFAULT,
+    }
+}
+
+fn plain_preview_line(message: &str) -> Vec<Line<'static>> {
+    vec![Line::from(Span::styled(
+        message.to_string(),
+        Style::default().fg(Color::DarkGray),
+    ))]
+}
+
+/// Renders the first [`PREVIEW_MAX_LINES`] of `raw`'s target file, syntax-highlighted by
+/// extension, falling back to a plain message for missing, binary, or non-UTF-8 files.
+fn render_preview(
+    raw: &str,
+    mode: SearchMode,
+    syntax_set: &SyntaxSet,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let path = PathBuf::from(preview_target_path(mode, raw));
+
+    if !path.is_file() {
+        return plain_preview_line("(không tìm thấy file để preview)");
+    }
+    if looks_binary(&path) {
+        return plain_preview_line("(binary file, không preview được)");
+    }
+    let Ok(content) = fs::read_to_string(&path) else {
+        return plain_preview_line("(không đọc được file, có thể không phải UTF-8)");
+    };
+
+    let syntax = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| syntax_set.find_syntax_by_extension(ext))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(&content)
+        .take(PREVIEW_MAX_LINES)
+        .map(|line| match highlighter.highlight_line(line, syntax_set) {
+            Ok(ranges) => Line::from(
+                ranges
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let fg = style.foreground;
+                        Span::styled(
+                            text.trim_end_matches(['\n', '\r']).to_string(),
+                            Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b)),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => Line::from(line.trim_end_matches(['\n', '\r']).to_string()),
         })
-        .collect::<Vec<String>>();
+        .collect()
+}
 
-    let mut matched = matched;
-    matched.sort_unstable();
-    matched.dedup();
+fn search_files_content(
+    query: &str,
+    roots: &[PathBuf],
+    max_results: usize,
+    follow_symlinks: bool,
+    tx: &Sender<(u64, SearchMsg)>,
+    generation: u64,
+    cancel: &AtomicBool,
+) -> SearchOutput {
+    let query_lower = query.to_lowercase();
+    let scanned = AtomicUsize::new(0);
+    let hits = AtomicUsize::new(0);
+    let batcher = HitBatcher::new(tx, generation);
+
+    let work_items: Vec<PathBuf> = roots.iter().flat_map(|r| root_work_items(r)).collect();
+
+    let grep_file = |path: &Path| {
+        if hits.load(Ordering::Relaxed) >= max_results {
+            return;
+        }
+
+        let Ok(metadata) = fs::metadata(path) else {
+            return;
+        };
+        if metadata.len() > CONTENT_MAX_FILE_BYTES || looks_binary(path) {
+            return;
+        }
+
+        let Ok(file) = fs::File::open(path) else {
+            return;
+        };
+
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            if cancel.load(Ordering::Relaxed) || hits.load(Ordering::Relaxed) >= max_results {
+                break;
+            }
+            let Ok(line) = line else {
+                continue;
+            };
+            if line.to_lowercase().contains(&query_lower) {
+                let prev = hits.fetch_add(1, Ordering::Relaxed);
+                if prev < max_results {
+                    let entry = format!("{}:{}: {}", path.display(), idx + 1, line.trim());
+                    batcher.push((0i64, entry));
+                }
+            }
+        }
+    };
+
+    work_items.into_iter().par_bridge().for_each(|item| {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        if item.is_file() {
+            record_scan(&scanned, tx, generation);
+            grep_file(&item);
+            return;
+        }
+
+        WalkDir::new(item)
+            .follow_links(follow_symlinks)
+            .into_iter()
+            .filter_map(Result::ok)
+            .take_while(|_| !cancel.load(Ordering::Relaxed))
+            .filter(|entry| entry.file_type().is_file())
+            .for_each(|entry| {
+                record_scan(&scanned, tx, generation);
+                grep_file(entry.path());
+            });
+    });
+
+    batcher.flush_remaining();
 
     SearchOutput {
         scanned: scanned.load(Ordering::Relaxed),
-        matched,
     }
 }
 
@@ -377,18 +1365,21 @@ fn draw(frame: &mut Frame, app: &App) {
         ])
         .split(frame.area());
 
-    let title = Paragraph::new("File Search TUI • Include Match • Multi-thread")
-        .style(Style::default().fg(Color::LightMagenta))
-        .block(Block::default().title("Overview").borders(Borders::ALL));
+    let title = Paragraph::new(format!(
+        "File Search TUI • {} strategy • Multi-thread",
+        app.mode.label()
+    ))
+    .style(Style::default().fg(app.config.theme.foreground))
+    .block(Block::default().title("Overview").borders(Borders::ALL));
     frame.render_widget(title, chunks[0]);
 
     let query_style = if app.focus == Focus::Query {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.config.theme.focus)
     } else {
         Style::default()
     };
     let root_style = if app.focus == Focus::Root {
-        Style::default().fg(Color::Yellow)
+        Style::default().fg(app.config.theme.focus)
     } else {
         Style::default()
     };
@@ -400,7 +1391,11 @@ fn draw(frame: &mut Frame, app: &App) {
 
     let query_box = Paragraph::new(app.query.clone())
         .style(query_style)
-        .block(Block::default().title("Query (include)").borders(Borders::ALL));
+        .block(
+            Block::default()
+                .title(format!("Query ({})", app.mode.label()))
+                .borders(Borders::ALL),
+        );
     frame.render_widget(query_box, chunks[1]);
 
     let root_placeholder = if app.root.trim().is_empty() {
@@ -415,38 +1410,72 @@ fn draw(frame: &mut Frame, app: &App) {
 
     let status_box = Paragraph::new(app.status_line())
         .style(if app.searching {
-            Style::default().fg(Color::Yellow)
+            Style::default().fg(app.config.theme.focus)
         } else {
-            Style::default().fg(Color::Cyan)
+            Style::default().fg(app.config.theme.status)
         })
         .block(Block::default().title("Status").borders(Borders::ALL));
     frame.render_widget(status_box, chunks[3]);
 
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(chunks[4]);
+
     let items: Vec<ListItem> = if app.results.is_empty() {
         vec![ListItem::new(Line::from("(chưa có kết quả)".dark_gray()))]
     } else {
         app.results
             .iter()
-            .map(|s| ListItem::new(Line::from(s.clone())))
+            .enumerate()
+            .map(|(idx, s)| {
+                if !app.icons_enabled {
+                    return ListItem::new(Line::from(s.clone()));
+                }
+                let icon = app
+                    .result_icons
+                    .get(idx)
+                    .copied()
+                    .unwrap_or(ICON_DEFAULT);
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", icon.glyph), Style::default().fg(icon.color)),
+                    Span::raw(s.clone()),
+                ]))
+            })
             .collect()
     };
 
     let list = List::new(items)
-        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White))
+        .highlight_style(
+            Style::default()
+                .bg(app.config.theme.highlight_bg)
+                .fg(app.config.theme.highlight_fg),
+        )
         .highlight_symbol("▶ ")
         .block(Block::default().title(results_title).borders(Borders::ALL));
     let mut list_state = app.results_state.clone();
-    frame.render_stateful_widget(list, chunks[4], &mut list_state);
+    frame.render_stateful_widget(list, body[0], &mut list_state);
+
+    let preview_lines: Vec<Line> = if app.preview_lines.is_empty() {
+        vec![Line::from("(chọn 1 kết quả để preview)".dark_gray())]
+    } else {
+        app.preview_lines.clone()
+    };
+    let preview = Paragraph::new(preview_lines)
+        .block(Block::default().title("Preview").borders(Borders::ALL));
+    frame.render_widget(preview, body[1]);
 }
 
 fn run_app(terminal: &mut DefaultTerminal) -> io::Result<()> {
-    let mut app = App::default();
+    let mut app = App::new();
 
     loop {
         app.tick();
+        app.drain_fs_events();
+        app.update_preview();
         terminal.draw(|frame| draw(frame, &app))?;
 
-        if !event::poll(Duration::from_millis(120))? {
+        if !event::poll(app.config.poll_interval)? {
             continue;
         }
 
@@ -456,7 +1485,15 @@ fn run_app(terminal: &mut DefaultTerminal) -> io::Result<()> {
             }
 
             match key.code {
-                KeyCode::Esc => break,
+                KeyCode::Esc => {
+                    if app.searching {
+                        app.cancel_search();
+                    } else {
+                        break;
+                    }
+                }
+                KeyCode::F(2) => app.toggle_mode(),
+                KeyCode::F(3) => app.toggle_icons(),
                 KeyCode::Tab => {
                     app.focus = match app.focus {
                         Focus::Query => Focus::Root,
@@ -513,4 +1550,131 @@ fn main() -> io::Result<()> {
     disable_raw_mode()?;
 
     result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_rejects_out_of_order_or_missing_chars() {
+        assert_eq!(fuzzy_score("abc", "xyz"), None);
+        assert_eq!(fuzzy_score("abc", "cba"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_consecutive_runs_over_scattered_matches() {
+        // Use a non-boundary filler char ('X') for the scattered case: a boundary
+        // char like '_' here would also earn the (larger, by design) boundary
+        // bonus, which isn't what this test is isolating.
+        let consecutive = fuzzy_score("ab", "ab").unwrap();
+        let scattered = fuzzy_score("ab", "aXb").unwrap();
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_word_boundary_matches() {
+        let at_boundary = fuzzy_score("b", "a_b").unwrap();
+        let mid_word = fuzzy_score("b", "ab").unwrap();
+        assert!(at_boundary > mid_word);
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_camel_case_boundary_on_original_case_candidate() {
+        let boundary = fuzzy_score("fb", "fooBar").unwrap();
+        let no_boundary = fuzzy_score("fb", "foobar").unwrap();
+        assert!(boundary > no_boundary);
+    }
+
+    #[test]
+    fn fuzzy_score_penalizes_gaps_between_matches() {
+        let tight = fuzzy_score("ab", "aXb").unwrap();
+        let loose = fuzzy_score("ab", "aXXb").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn preview_target_path_keeps_windows_drive_letter() {
+        let raw = "C:\\Users\\me\\project\\src\\main.rs:42: let x = 1;";
+        assert_eq!(
+            preview_target_path(SearchMode::Content, raw),
+            "C:\\Users\\me\\project\\src\\main.rs"
+        );
+    }
+
+    #[test]
+    fn preview_target_path_handles_unix_paths() {
+        let raw = "/home/me/project/src/main.rs:42: let x = 1;";
+        assert_eq!(
+            preview_target_path(SearchMode::Content, raw),
+            "/home/me/project/src/main.rs"
+        );
+    }
+
+    #[test]
+    fn parse_color_reads_hex() {
+        assert_eq!(parse_color("#ff0000"), Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(parse_color("#00ff80"), Some(Color::Rgb(0, 255, 128)));
+    }
+
+    #[test]
+    fn parse_color_reads_named_colors_case_insensitively() {
+        assert_eq!(parse_color("Cyan"), Some(Color::Cyan));
+        assert_eq!(parse_color("LIGHTMAGENTA"), Some(Color::LightMagenta));
+        assert_eq!(parse_color("grey"), Some(Color::Gray));
+    }
+
+    #[test]
+    fn parse_color_rejects_garbage_input() {
+        assert_eq!(parse_color("#ff00"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+        assert_eq!(parse_color("not-a-color"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    #[test]
+    fn config_merge_overrides_only_present_valid_fields() {
+        let defaults = Config::default();
+        let file = ConfigFile {
+            foreground: Some("red".to_string()),
+            max_results: Some(42),
+            ..ConfigFile::default()
+        };
+
+        let merged = Config::merge(defaults.clone(), file);
+
+        assert_eq!(merged.theme.foreground, Color::Red);
+        assert_eq!(merged.max_results, 42);
+        // Everything not mentioned in `file` falls back to the default.
+        assert_eq!(merged.theme.focus, defaults.theme.focus);
+        assert_eq!(merged.poll_interval, defaults.poll_interval);
+        assert_eq!(merged.default_root, defaults.default_root);
+        assert_eq!(merged.follow_symlinks, defaults.follow_symlinks);
+    }
+
+    #[test]
+    fn config_merge_falls_back_on_invalid_color() {
+        let defaults = Config::default();
+        let file = ConfigFile {
+            foreground: Some("not-a-color".to_string()),
+            ..ConfigFile::default()
+        };
+
+        let merged = Config::merge(defaults.clone(), file);
+
+        assert_eq!(merged.theme.foreground, defaults.theme.foreground);
+    }
+
+    #[test]
+    fn config_merge_falls_back_on_empty_spinner() {
+        let defaults = Config::default();
+        let file = ConfigFile {
+            spinner: Some(Vec::new()),
+            ..ConfigFile::default()
+        };
+
+        let merged = Config::merge(defaults.clone(), file);
+
+        assert_eq!(merged.theme.spinner, defaults.theme.spinner);
+    }
 }
\ No newline at end of file